@@ -0,0 +1,260 @@
+//! On-disk inode layout: the fixed-size record stored in a filesystem block, plus the
+//! (direct + single-indirect) block-pointer scheme used to find a file's data blocks.
+use super::{get_block_cache, BlockDevice, BLOCK_SZ};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+/// Direct block pointers held inline in the inode
+const INODE_DIRECT_COUNT: usize = 24;
+/// Block pointers held in the one indirect block
+const INODE_INDIRECT1_COUNT: usize = BLOCK_SZ / 4;
+const DIRECT_BOUND: usize = INODE_DIRECT_COUNT;
+const INDIRECT1_BOUND: usize = DIRECT_BOUND + INODE_INDIRECT1_COUNT;
+
+type IndirectBlock = [u32; BLOCK_SZ / 4];
+
+/// What kind of file a [`DiskInode`] describes
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum DiskInodeType {
+    /// a regular file
+    File,
+    /// a directory, whose data is a packed array of [`super::DirEntry`]
+    Directory,
+    /// a symlink, whose data is the (unresolved) target path as UTF-8 bytes
+    Symlink,
+}
+
+/// The on-disk representation of an inode: everything but its data blocks and name
+/// (the latter lives in its parent directory's dirent).
+#[repr(C)]
+pub struct DiskInode {
+    /// data size in bytes
+    pub size: u32,
+    /// direct data block pointers
+    pub direct: [u32; INODE_DIRECT_COUNT],
+    /// indirect (single level) data block pointer
+    pub indirect1: u32,
+    /// reserved for a second indirection level; currently always 0 (unused)
+    pub indirect2: u32,
+    type_: DiskInodeType,
+    /// hard-link count
+    pub nlinks: u32,
+    /// rwx + file-type bits, POSIX `st_mode` style
+    pub mode: u16,
+    /// owning user id
+    pub uid: u32,
+    /// owning group id
+    pub gid: u32,
+    /// last-access timestamp
+    pub atime: usize,
+    /// last-modification timestamp
+    pub mtime: usize,
+    /// last-metadata-change timestamp
+    pub ctime: usize,
+    /// block holding this inode's packed extended attributes, or 0 if none allocated
+    pub xattr_block: u32,
+}
+
+impl DiskInode {
+    /// Initialize a freshly allocated inode as `type_`, empty, with `mode`/`uid`/`gid`
+    /// zeroed (uid/gid 0 is root); the caller (`Inode::create`/`mkdir`/`symlink`) fills
+    /// them in afterwards.
+    pub fn initialize(&mut self, type_: DiskInodeType) {
+        self.size = 0;
+        self.direct = [0; INODE_DIRECT_COUNT];
+        self.indirect1 = 0;
+        self.indirect2 = 0;
+        self.type_ = type_;
+        self.nlinks = 1;
+        self.mode = 0;
+        self.uid = 0;
+        self.gid = 0;
+        self.atime = 0;
+        self.mtime = 0;
+        self.ctime = 0;
+        self.xattr_block = 0;
+    }
+    /// Is this inode a regular file?
+    pub fn is_file(&self) -> bool {
+        self.type_ == DiskInodeType::File
+    }
+    /// Is this inode a directory?
+    pub fn is_dir(&self) -> bool {
+        self.type_ == DiskInodeType::Directory
+    }
+    /// Is this inode a symlink?
+    pub fn is_symlink(&self) -> bool {
+        self.type_ == DiskInodeType::Symlink
+    }
+    fn get_block_id(&self, inner_id: u32, block_device: &Arc<dyn BlockDevice>) -> u32 {
+        let inner_id = inner_id as usize;
+        if inner_id < DIRECT_BOUND {
+            self.direct[inner_id]
+        } else if inner_id < INDIRECT1_BOUND {
+            get_block_cache(self.indirect1 as usize, Arc::clone(block_device))
+                .lock()
+                .read(0, |indirect_block: &IndirectBlock| {
+                    indirect_block[inner_id - DIRECT_BOUND]
+                })
+        } else {
+            panic!("file too large for the direct+indirect1 block scheme");
+        }
+    }
+    /// Number of data blocks `size` bytes need, direct+indirect1 data blocks only
+    pub fn data_blocks(size: u32) -> u32 {
+        (size as usize + BLOCK_SZ - 1) / BLOCK_SZ
+    }
+    /// Total blocks needed to hold `size` bytes, including the indirect1 pointer block
+    pub fn total_blocks(size: u32) -> u32 {
+        let data_blocks = Self::data_blocks(size);
+        let mut total = data_blocks;
+        if data_blocks as usize > INODE_DIRECT_COUNT {
+            total += 1;
+        }
+        total
+    }
+    /// Additional blocks needed to grow from the current size to `new_size`
+    pub fn blocks_num_needed(&self, new_size: u32) -> u32 {
+        Self::total_blocks(new_size) - Self::total_blocks(self.size)
+    }
+    /// Grow to `new_size`, consuming freshly allocated block ids from `new_blocks` (as
+    /// many as `blocks_num_needed` said were required)
+    pub fn increase_size(
+        &mut self,
+        new_size: u32,
+        new_blocks: Vec<u32>,
+        block_device: &Arc<dyn BlockDevice>,
+    ) {
+        let mut current_blocks = Self::data_blocks(self.size);
+        self.size = new_size;
+        let mut total_blocks = Self::data_blocks(new_size);
+        let mut new_blocks = new_blocks.into_iter();
+
+        while current_blocks < total_blocks.min(INODE_DIRECT_COUNT as u32) {
+            self.direct[current_blocks as usize] = new_blocks.next().unwrap();
+            current_blocks += 1;
+        }
+        if total_blocks as usize <= INODE_DIRECT_COUNT {
+            return;
+        }
+        if current_blocks == INODE_DIRECT_COUNT as u32 && self.indirect1 == 0 {
+            self.indirect1 = new_blocks.next().unwrap();
+        }
+        current_blocks -= INODE_DIRECT_COUNT as u32;
+        total_blocks -= INODE_DIRECT_COUNT as u32;
+        get_block_cache(self.indirect1 as usize, Arc::clone(block_device))
+            .lock()
+            .modify(0, |indirect_block: &mut IndirectBlock| {
+                while current_blocks < total_blocks {
+                    indirect_block[current_blocks as usize] = new_blocks.next().unwrap();
+                    current_blocks += 1;
+                }
+            });
+    }
+    /// Shrink to empty, returning every data block id it held (for the caller to
+    /// deallocate) — the indirect1 pointer block itself is included.
+    /// Note: this does not touch `xattr_block`; the caller is responsible for freeing
+    /// that separately once it knows no other reference to the inode remains.
+    pub fn clear_size(&mut self, block_device: &Arc<dyn BlockDevice>) -> Vec<u32> {
+        let mut v: Vec<u32> = Vec::new();
+        let data_blocks = Self::data_blocks(self.size) as usize;
+        let mut current_blocks = 0usize;
+        while current_blocks < data_blocks.min(INODE_DIRECT_COUNT) {
+            v.push(self.direct[current_blocks]);
+            self.direct[current_blocks] = 0;
+            current_blocks += 1;
+        }
+        if data_blocks > INODE_DIRECT_COUNT {
+            get_block_cache(self.indirect1 as usize, Arc::clone(block_device))
+                .lock()
+                .modify(0, |indirect_block: &mut IndirectBlock| {
+                    for i in 0..(data_blocks - INODE_DIRECT_COUNT) {
+                        v.push(indirect_block[i]);
+                    }
+                });
+            v.push(self.indirect1);
+            self.indirect1 = 0;
+        }
+        self.size = 0;
+        v
+    }
+    /// Read up to `buf.len()` bytes starting at `offset`, returning the number actually read
+    pub fn read_at(
+        &self,
+        offset: usize,
+        buf: &mut [u8],
+        block_device: &Arc<dyn BlockDevice>,
+    ) -> usize {
+        let size = self.size as usize;
+        if offset >= size {
+            return 0;
+        }
+        let end = (offset + buf.len()).min(size);
+        let mut start = offset;
+        let mut start_block = start / BLOCK_SZ;
+        let mut read_size = 0usize;
+        loop {
+            let mut end_current_block = (start / BLOCK_SZ + 1) * BLOCK_SZ;
+            end_current_block = end_current_block.min(end);
+            let block_read_size = end_current_block - start;
+            let dst = &mut buf[read_size..read_size + block_read_size];
+            get_block_cache(
+                self.get_block_id(start_block as u32, block_device) as usize,
+                Arc::clone(block_device),
+            )
+            .lock()
+            .read(0, |data_block: &[u8; BLOCK_SZ]| {
+                let src = &data_block[start % BLOCK_SZ..start % BLOCK_SZ + block_read_size];
+                dst.copy_from_slice(src);
+            });
+            read_size += block_read_size;
+            if end_current_block == end {
+                break;
+            }
+            start_block += 1;
+            start = end_current_block;
+        }
+        read_size
+    }
+    /// Write `buf` at `offset`; the caller must already have grown the inode (via
+    /// `increase_size`) to cover `offset + buf.len()`. Returns the number of bytes written.
+    pub fn write_at(
+        &mut self,
+        offset: usize,
+        buf: &[u8],
+        block_device: &Arc<dyn BlockDevice>,
+    ) -> usize {
+        let size = self.size as usize;
+        let end = (offset + buf.len()).min(size);
+        assert!(start_le_end(offset, end));
+        let mut start = offset;
+        let mut start_block = start / BLOCK_SZ;
+        let mut write_size = 0usize;
+        loop {
+            let mut end_current_block = (start / BLOCK_SZ + 1) * BLOCK_SZ;
+            end_current_block = end_current_block.min(end);
+            let block_write_size = end_current_block - start;
+            get_block_cache(
+                self.get_block_id(start_block as u32, block_device) as usize,
+                Arc::clone(block_device),
+            )
+            .lock()
+            .modify(0, |data_block: &mut [u8; BLOCK_SZ]| {
+                let src = &buf[write_size..write_size + block_write_size];
+                let dst = &mut data_block[start % BLOCK_SZ..start % BLOCK_SZ + block_write_size];
+                dst.copy_from_slice(src);
+            });
+            write_size += block_write_size;
+            if end_current_block == end {
+                break;
+            }
+            start_block += 1;
+            start = end_current_block;
+        }
+        write_size
+    }
+}
+
+fn start_le_end(start: usize, end: usize) -> bool {
+    start <= end
+}
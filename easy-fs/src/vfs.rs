@@ -1,13 +1,61 @@
 use super::{
     block_cache_sync_all, get_block_cache, BlockDevice, DirEntry, DiskInode, DiskInodeType,
-    EasyFileSystem, DIRENT_SZ,
+    EasyFileSystem, BLOCK_SZ, DIRENT_SZ,
 };
 use alloc::string::String;
 use alloc::sync::Arc;
 use alloc::vec::Vec;
+use core::num::NonZeroUsize;
+use lazy_static::lazy_static;
 use spin::{Mutex, MutexGuard};
+
+/// Default capacity of the global [`InodeCache`]
+const INODE_CACHE_CAPACITY: usize = 64;
+
+/// LRU cache of live `Inode` handles, keyed by `inode_id`, so repeated lookups of the same
+/// file (e.g. through a hard link) yield the same `Arc<Inode>` instead of a fresh one.
+/// Eviction is safe: the underlying block cache still holds the actual data.
+struct InodeCache {
+    cap: NonZeroUsize,
+    // front = most recently used
+    entries: Vec<(u32, Arc<Inode>)>,
+}
+
+impl InodeCache {
+    fn new(cap: NonZeroUsize) -> Self {
+        Self {
+            cap,
+            entries: Vec::new(),
+        }
+    }
+    fn get(&mut self, inode_id: u32) -> Option<Arc<Inode>> {
+        let pos = self.entries.iter().position(|(id, _)| *id == inode_id)?;
+        let (_, inode) = self.entries.remove(pos);
+        self.entries.insert(0, (inode_id, inode.clone()));
+        Some(inode)
+    }
+    fn put(&mut self, inode_id: u32, inode: Arc<Inode>) {
+        self.entries.retain(|(id, _)| *id != inode_id);
+        self.entries.insert(0, (inode_id, inode));
+        while self.entries.len() > self.cap.get() {
+            self.entries.pop();
+        }
+    }
+    /// Evict `inode_id`'s cached handle, if any — called whenever its inode is freed so a
+    /// later reallocation of the same inode_id can't alias a stale handle
+    fn remove(&mut self, inode_id: u32) {
+        self.entries.retain(|(id, _)| *id != inode_id);
+    }
+}
+
+lazy_static! {
+    static ref INODE_CACHE: Mutex<InodeCache> =
+        Mutex::new(InodeCache::new(NonZeroUsize::new(INODE_CACHE_CAPACITY).unwrap()));
+}
+
 /// Virtual filesystem layer over easy-fs
 pub struct Inode {
+    inode_id: u32,
     block_id: usize,
     block_offset: usize,
     fs: Arc<Mutex<EasyFileSystem>>,
@@ -17,18 +65,35 @@ pub struct Inode {
 impl Inode {
     /// Create a vfs inode
     pub fn new(
+        inode_id: u32,
         block_id: u32,
         block_offset: usize,
         fs: Arc<Mutex<EasyFileSystem>>,
         block_device: Arc<dyn BlockDevice>,
     ) -> Self {
         Self {
+            inode_id,
             block_id: block_id as usize,
             block_offset,
             fs,
             block_device,
         }
     }
+    /// Get a shared handle for `inode_id`, reusing one from the [`InodeCache`] if present
+    fn from_cache(
+        inode_id: u32,
+        block_id: u32,
+        block_offset: usize,
+        fs: Arc<Mutex<EasyFileSystem>>,
+        block_device: Arc<dyn BlockDevice>,
+    ) -> Arc<Inode> {
+        if let Some(hit) = INODE_CACHE.lock().get(inode_id) {
+            return hit;
+        }
+        let inode = Arc::new(Self::new(inode_id, block_id, block_offset, fs, block_device));
+        INODE_CACHE.lock().put(inode_id, inode.clone());
+        inode
+    }
     /// Call a function over a disk inode to read it
     pub fn read_disk_inode<V>(&self, f: impl FnOnce(&DiskInode) -> V) -> V {
         get_block_cache(self.block_id, Arc::clone(&self.block_device))
@@ -41,10 +106,60 @@ impl Inode {
             .lock()
             .modify(self.block_offset, f)
     }
+    /// bit flags for `check_access`'s `want` parameter
+    pub const ACCESS_R: u8 = 0b100;
+    ///
+    pub const ACCESS_W: u8 = 0b010;
+    ///
+    pub const ACCESS_X: u8 = 0b001;
+    /// Change the mode (file-type + rwx bits) of current inode. `now` is the caller's
+    /// current time (easy-fs is a standalone, no_std crate with no clock of its own —
+    /// the `os` crate passes `crate::timer::now()` in).
+    pub fn chmod(&self, mode: u16, now: usize) {
+        let _fs = self.fs.lock();
+        self.modify_disk_inode(|disk_inode| {
+            disk_inode.mode = mode;
+            disk_inode.ctime = now;
+        });
+        block_cache_sync_all();
+    }
+    /// Change the owning uid/gid of current inode. See [`Inode::chmod`] re: `now`.
+    pub fn chown(&self, uid: u32, gid: u32, now: usize) {
+        let _fs = self.fs.lock();
+        self.modify_disk_inode(|disk_inode| {
+            disk_inode.uid = uid;
+            disk_inode.gid = gid;
+            disk_inode.ctime = now;
+        });
+        block_cache_sync_all();
+    }
+    /// Check whether `uid`/`gids` may access current inode with the rwx bits in `want`
+    pub fn check_access(&self, uid: u32, gids: &[u32], want: u8) -> bool {
+        self.read_disk_inode(|disk_inode| {
+            if uid == 0 {
+                // root can do anything, except execute a file with no x bit at all
+                if want & Self::ACCESS_X != 0 {
+                    return disk_inode.mode & 0o111 != 0;
+                }
+                return true;
+            }
+            let triad = if uid == disk_inode.uid {
+                (disk_inode.mode >> 6) & 0o7
+            } else if gids.contains(&disk_inode.gid) {
+                (disk_inode.mode >> 3) & 0o7
+            } else {
+                disk_inode.mode & 0o7
+            };
+            (triad as u8) & want == want
+        })
+    }
     /// Find inode under a disk inode by name
     pub fn find_inode_id(&self, name: &str, disk_inode: &DiskInode) -> Option<u32> {
-        // assert it is a directory
-        assert!(disk_inode.is_dir());
+        // a non-directory has no entries to look up; a bad user-supplied path should
+        // fail the lookup rather than panic the kernel
+        if !disk_inode.is_dir() {
+            return None;
+        }
         let file_count = (disk_inode.size as usize) / DIRENT_SZ;
         let mut dirent = DirEntry::empty();
         for i in 0..file_count {
@@ -64,12 +179,13 @@ impl Inode {
         self.read_disk_inode(|disk_inode| {
             self.find_inode_id(name, disk_inode).map(|inode_id| {
                 let (block_id, block_offset) = fs.get_disk_inode_pos(inode_id);
-                Arc::new(Self::new(
+                Self::from_cache(
+                    inode_id,
                     block_id,
                     block_offset,
                     self.fs.clone(),
                     self.block_device.clone(),
-                ))
+                )
             })
         })
     }
@@ -90,8 +206,8 @@ impl Inode {
         }
         disk_inode.increase_size(new_size, v, &self.block_device);
     }
-    /// Create inode under current inode by name
-    pub fn create(&self, name: &str) -> Option<Arc<Inode>> {
+    /// Create inode under current inode by name, owned by `uid`/`gid`
+    pub fn create(&self, name: &str, uid: u32, gid: u32) -> Option<Arc<Inode>> {
         let mut fs = self.fs.lock();
         let op = |root_inode: &DiskInode| {
             // assert it is a directory
@@ -111,6 +227,10 @@ impl Inode {
             .lock()
             .modify(new_inode_block_offset, |new_inode: &mut DiskInode| {
                 new_inode.initialize(DiskInodeType::File);
+                // default to rw-r--r--, caller can chmod afterwards
+                new_inode.mode = 0o644;
+                new_inode.uid = uid;
+                new_inode.gid = gid;
             });
         self.modify_disk_inode(|root_inode| {
             // append file in the dirent
@@ -130,14 +250,215 @@ impl Inode {
         let (block_id, block_offset) = fs.get_disk_inode_pos(new_inode_id);
         block_cache_sync_all();
         // return inode
-        Some(Arc::new(Self::new(
+        Some(Self::from_cache(
+            new_inode_id,
             block_id,
             block_offset,
             self.fs.clone(),
             self.block_device.clone(),
-        )))
+        ))
         // release efs lock automatically by compiler
     }
+    /// Create a directory under current inode, seeded with `.`/`..` dirents
+    pub fn mkdir(&self, name: &str, parent_inode_id: u32, uid: u32, gid: u32) -> Option<Arc<Inode>> {
+        let mut fs = self.fs.lock();
+        let op = |root_inode: &DiskInode| {
+            assert!(root_inode.is_dir());
+            self.find_inode_id(name, root_inode)
+        };
+        if self.read_disk_inode(op).is_some() {
+            return None;
+        }
+        let new_inode_id = fs.alloc_inode();
+        let (new_inode_block_id, new_inode_block_offset) = fs.get_disk_inode_pos(new_inode_id);
+        get_block_cache(new_inode_block_id as usize, Arc::clone(&self.block_device))
+            .lock()
+            .modify(new_inode_block_offset, |new_inode: &mut DiskInode| {
+                new_inode.initialize(DiskInodeType::Directory);
+                new_inode.mode = 0o755;
+                new_inode.uid = uid;
+                new_inode.gid = gid;
+            });
+        self.modify_disk_inode(|root_inode| {
+            let file_count = (root_inode.size as usize) / DIRENT_SZ;
+            let new_size = (file_count + 1) * DIRENT_SZ;
+            self.increase_size(new_size as u32, root_inode, &mut fs);
+            let dirent = DirEntry::new(name, new_inode_id);
+            root_inode.write_at(
+                file_count * DIRENT_SZ,
+                dirent.as_bytes(),
+                &self.block_device,
+            );
+        });
+        let new_inode = Self::new(
+            new_inode_id,
+            new_inode_block_id,
+            new_inode_block_offset,
+            self.fs.clone(),
+            self.block_device.clone(),
+        );
+        // seed `.` and `..`
+        let this_dirent = DirEntry::new(".", new_inode_id);
+        let parent_dirent = DirEntry::new("..", parent_inode_id);
+        new_inode.modify_disk_inode(|new_disk_inode| {
+            self.increase_size(2 * DIRENT_SZ as u32, new_disk_inode, &mut fs);
+            new_disk_inode.write_at(0, this_dirent.as_bytes(), &self.block_device);
+            new_disk_inode.write_at(DIRENT_SZ, parent_dirent.as_bytes(), &self.block_device);
+        });
+        block_cache_sync_all();
+        Some(Self::from_cache(
+            new_inode_id,
+            new_inode_block_id,
+            new_inode_block_offset,
+            self.fs.clone(),
+            self.block_device.clone(),
+        ))
+    }
+    /// Walk a `/`-separated path starting from current inode, returning the target inode.
+    /// Symlinks encountered along the way are transparently resolved.
+    pub fn find_path(&self, path: &str) -> Option<Arc<Inode>> {
+        self.find_path_with_hops(path, 0)
+    }
+    /// Maximum number of symlink hops `find_path` will follow before giving up (cycle guard)
+    const MAX_SYMLINK_HOPS: u32 = 40;
+    fn find_path_with_hops(&self, path: &str, hops: u32) -> Option<Arc<Inode>> {
+        if hops > Self::MAX_SYMLINK_HOPS {
+            return None;
+        }
+        // go through the cache rather than dup()'ing a fresh handle, so that a path
+        // resolving to `self` (e.g. ".", "") returns the same Arc as every other lookup
+        // of this inode_id instead of a one-off handle that breaks the identity guarantee
+        let mut cur = Self::from_cache(
+            self.inode_id,
+            self.block_id as u32,
+            self.block_offset,
+            self.fs.clone(),
+            self.block_device.clone(),
+        );
+        for comp in path.split('/') {
+            if comp.is_empty() || comp == "." {
+                continue;
+            }
+            let is_dir = cur.read_disk_inode(|disk_inode| disk_inode.is_dir());
+            if !is_dir {
+                // a non-directory component partway through the path: invalid, not a bug
+                return None;
+            }
+            let parent = cur.clone();
+            let next = cur.find(comp)?;
+            let is_symlink = next.read_disk_inode(|disk_inode| disk_inode.is_symlink());
+            cur = if is_symlink {
+                let target = next.read_link()?;
+                // resolve relative to the symlink's *parent* directory, not the symlink
+                // itself — the symlink inode is never a directory, so resolving against
+                // it would always fail the `is_dir` check above on the target's first
+                // component
+                parent.find_path_with_hops(&target, hops + 1)?
+            } else {
+                next
+            };
+        }
+        Some(cur)
+    }
+    /// Create a symlink under current inode pointing at `target`
+    pub fn symlink(&self, name: &str, target: &str, uid: u32, gid: u32) -> Option<Arc<Inode>> {
+        let mut fs = self.fs.lock();
+        let op = |root_inode: &DiskInode| {
+            assert!(root_inode.is_dir());
+            self.find_inode_id(name, root_inode)
+        };
+        if self.read_disk_inode(op).is_some() {
+            return None;
+        }
+        let new_inode_id = fs.alloc_inode();
+        let (new_inode_block_id, new_inode_block_offset) = fs.get_disk_inode_pos(new_inode_id);
+        get_block_cache(new_inode_block_id as usize, Arc::clone(&self.block_device))
+            .lock()
+            .modify(new_inode_block_offset, |new_inode: &mut DiskInode| {
+                new_inode.initialize(DiskInodeType::Symlink);
+                new_inode.mode = 0o777;
+                new_inode.uid = uid;
+                new_inode.gid = gid;
+            });
+        self.modify_disk_inode(|root_inode| {
+            let file_count = (root_inode.size as usize) / DIRENT_SZ;
+            let new_size = (file_count + 1) * DIRENT_SZ;
+            self.increase_size(new_size as u32, root_inode, &mut fs);
+            let dirent = DirEntry::new(name, new_inode_id);
+            root_inode.write_at(
+                file_count * DIRENT_SZ,
+                dirent.as_bytes(),
+                &self.block_device,
+            );
+        });
+        let new_inode = Self::new(
+            new_inode_id,
+            new_inode_block_id,
+            new_inode_block_offset,
+            self.fs.clone(),
+            self.block_device.clone(),
+        );
+        new_inode.modify_disk_inode(|disk_inode| {
+            self.increase_size(target.len() as u32, disk_inode, &mut fs);
+            disk_inode.write_at(0, target.as_bytes(), &self.block_device);
+        });
+        block_cache_sync_all();
+        Some(Self::from_cache(
+            new_inode_id,
+            new_inode_block_id,
+            new_inode_block_offset,
+            self.fs.clone(),
+            self.block_device.clone(),
+        ))
+    }
+    /// Read the target of a symlink, if current inode is one
+    pub fn read_link(&self) -> Option<String> {
+        let _fs = self.fs.lock();
+        let (is_symlink, size) =
+            self.read_disk_inode(|disk_inode| (disk_inode.is_symlink(), disk_inode.size));
+        if !is_symlink {
+            return None;
+        }
+        let mut buf = alloc::vec![0u8; size as usize];
+        self.read_disk_inode(|disk_inode| disk_inode.read_at(0, &mut buf, &self.block_device));
+        String::from_utf8(buf).ok()
+    }
+    /// Remove an empty subdirectory by name (ENOTEMPTY-style refusal otherwise)
+    pub fn rmdir(&self, name: &str) -> isize {
+        let fs = self.fs.lock();
+        let inode_id = self.read_disk_inode(|disk_inode| self.find_inode_id(name, disk_inode));
+        let inode_id = match inode_id {
+            Some(id) => id,
+            None => return -1,
+        };
+        let (block_id, block_offset) = fs.get_disk_inode_pos(inode_id);
+        let target = Self::new(
+            inode_id,
+            block_id,
+            block_offset,
+            self.fs.clone(),
+            self.block_device.clone(),
+        );
+        let (is_dir, size) =
+            target.read_disk_inode(|disk_inode| (disk_inode.is_dir(), disk_inode.size));
+        if !is_dir {
+            return -1;
+        }
+        if size as usize > 2 * DIRENT_SZ {
+            // ENOTEMPTY
+            return -1;
+        }
+        drop(fs);
+        target.clear();
+        // the inode_id is now free for reuse; evict any cached handle so a later
+        // reallocation can't alias this stale one
+        INODE_CACHE.lock().remove(inode_id);
+        self.modify_disk_inode(|root_inode| {
+            self.modify_entry(name, root_inode);
+        });
+        block_cache_sync_all();
+        0
+    }
     /// List inodes under current inode
     pub fn ls(&self) -> Vec<String> {
         let _fs = self.fs.lock();
@@ -155,36 +476,76 @@ impl Inode {
             v
         })
     }
-    /// Read data from current inode
-    pub fn read_at(&self, offset: usize, buf: &mut [u8]) -> usize {
+    /// Read data from current inode, gated by `check_access` — the real I/O path every
+    /// caller (including `sys_read`) goes through, not just a parallel `_checked` helper.
+    /// `now` is the caller's current time; see [`Inode::chmod`].
+    pub fn read_at(
+        &self,
+        offset: usize,
+        buf: &mut [u8],
+        uid: u32,
+        gids: &[u32],
+        now: usize,
+    ) -> Option<usize> {
+        if !self.check_access(uid, gids, Self::ACCESS_R) {
+            return None;
+        }
         let _fs = self.fs.lock();
-        self.read_disk_inode(|disk_inode| disk_inode.read_at(offset, buf, &self.block_device))
+        let size = self.read_disk_inode(|disk_inode| disk_inode.read_at(offset, buf, &self.block_device));
+        self.modify_disk_inode(|disk_inode| {
+            disk_inode.atime = now;
+        });
+        block_cache_sync_all();
+        Some(size)
     }
-    /// Write data to current inode
-    pub fn write_at(&self, offset: usize, buf: &[u8]) -> usize {
+    /// Write data to current inode, gated by `check_access`; clears setuid/setgid bits
+    /// unless the writer is root. `now` is the caller's current time; see [`Inode::chmod`].
+    pub fn write_at(
+        &self,
+        offset: usize,
+        buf: &[u8],
+        uid: u32,
+        gids: &[u32],
+        now: usize,
+    ) -> Option<usize> {
+        if !self.check_access(uid, gids, Self::ACCESS_W) {
+            return None;
+        }
         let mut fs = self.fs.lock();
         let size = self.modify_disk_inode(|disk_inode| {
             self.increase_size((offset + buf.len()) as u32, disk_inode, &mut fs);
-            disk_inode.write_at(offset, buf, &self.block_device)
+            let written = disk_inode.write_at(offset, buf, &self.block_device);
+            disk_inode.mtime = now;
+            disk_inode.ctime = now;
+            if uid != 0 {
+                disk_inode.mode &= !0o6000;
+            }
+            written
         });
         block_cache_sync_all();
-        size
+        Some(size)
     }
     /// Clear the data in current inode
     pub fn clear(&self) {
         let mut fs = self.fs.lock();
-        self.modify_disk_inode(|disk_inode| {
+        let xattr_block = self.modify_disk_inode(|disk_inode| {
             let size = disk_inode.size;
             let data_blocks_dealloc = disk_inode.clear_size(&self.block_device);
             assert!(data_blocks_dealloc.len() == DiskInode::total_blocks(size) as usize);
             for data_block in data_blocks_dealloc.into_iter() {
                 fs.dealloc_data(data_block);
             }
+            let xattr_block = disk_inode.xattr_block;
+            disk_inode.xattr_block = 0;
+            xattr_block
         });
+        if xattr_block != 0 {
+            fs.dealloc_data(xattr_block);
+        }
         block_cache_sync_all();
     }
-    ///
-    pub fn linkat(&self,old_name:&str,new_name:&str)->isize{
+    /// `now` is the caller's current time; see [`Inode::chmod`].
+    pub fn linkat(&self,old_name:&str,new_name:&str,now:usize)->isize{
         if old_name==new_name{
             return -1;
         }
@@ -215,6 +576,7 @@ impl Inode {
         .lock()
         .modify(block_offset,|disk_inode:&mut DiskInode|{
             disk_inode.nlinks+=1;
+            disk_inode.ctime = now;
             disk_inode.nlinks
         });
         return 0;
@@ -246,21 +608,32 @@ impl Inode {
         return 0;
     }
      */
-    pub fn unlinkat(&self, name: &str) -> isize{
-        let fs = self.fs.lock();
+    /// `now` is the caller's current time; see [`Inode::chmod`].
+    pub fn unlinkat(&self, name: &str, now: usize) -> isize{
+        let mut fs = self.fs.lock();
         let inode_id = self.read_disk_inode(|disk_inode|{
             self.find_inode_id(name,disk_inode)
         });
         if let Some(i_id) = inode_id{
             let (block_id,block_offset) = fs.get_disk_inode_pos(i_id);
-            get_block_cache(block_id as usize, Arc::clone(&self.block_device)).lock()
+            let remaining_links = get_block_cache(block_id as usize, Arc::clone(&self.block_device)).lock()
             .modify(block_offset,|disk_inode:&mut DiskInode|{
                 disk_inode.nlinks -= 1;
+                disk_inode.ctime = now;
                 if disk_inode.nlinks == 0 {
                     disk_inode.clear_size(&Arc::clone(&self.block_device));
+                    if disk_inode.xattr_block != 0 {
+                        fs.dealloc_data(disk_inode.xattr_block);
+                        disk_inode.xattr_block = 0;
+                    }
                 }
-                disk_inode.nlinks  
+                disk_inode.nlinks
             });
+            if remaining_links == 0 {
+                // the inode_id is now free for reuse; evict any cached handle so a later
+                // reallocation can't alias this stale one
+                INODE_CACHE.lock().remove(i_id);
+            }
             self.modify_disk_inode(|root_inode|{
                 // let file_count = (root_inode.size as usize) / DIRENT_SZ;
                 // let mut dirent = DirEntry::empty();
@@ -284,22 +657,30 @@ impl Inode {
     }
 
     ///
-    pub fn state(&self,inode_id: u64)->(u32,bool){
-       // println!("here 5");
+    /// Returns (nlinks, is_file, is_symlink, atime, mtime, ctime) so callers can tell a hard
+    /// link from a symlink and report freshness (e.g. for `fstat`) — a `DiskInode` is always
+    /// exactly one of file/directory/symlink, so `!is_file && !is_symlink` means directory
+    pub fn state(&self,inode_id: u64)->(u32,bool,bool,usize,usize,usize){
         let mut nlink:u32 = 0;
         let mut is = true;
+        let mut is_symlink = false;
+        let mut atime = 0;
+        let mut mtime = 0;
+        let mut ctime = 0;
         let fs = self.fs.lock();
-       // println!("here 6");
         let (block_id,block_offset) = fs.get_disk_inode_pos(inode_id as u32);
         get_block_cache(block_id as usize, Arc::clone(&self.block_device))
         .lock()
         .modify(block_offset,|disk_inode:&mut DiskInode|{
             nlink = disk_inode.nlinks;
             is=disk_inode.is_file();
+            is_symlink=disk_inode.is_symlink();
+            atime = disk_inode.atime;
+            mtime = disk_inode.mtime;
+            ctime = disk_inode.ctime;
             0
         });
-       // println!("here 7");
-        (nlink,is)
+        (nlink,is,is_symlink,atime,mtime,ctime)
     }
     ///
     pub fn get_inode_id_from_name(&self,name:&str)->u32{
@@ -347,4 +728,217 @@ impl Inode {
         }
         */
     }
+    /// rewrite a dirent's inode-id in place without touching its position in the directory
+    fn set_entry_inode(&self, name: &str, new_inode_id: u32, disk_inode: &mut DiskInode) -> bool {
+        let file_count = (disk_inode.size as usize) / DIRENT_SZ;
+        let mut dirent = DirEntry::empty();
+        for i in 0..file_count {
+            disk_inode.read_at(i * DIRENT_SZ, dirent.as_bytes_mut(), &self.block_device);
+            if dirent.name() == name {
+                let new_dirent = DirEntry::new(name, new_inode_id);
+                disk_inode.write_at(i * DIRENT_SZ, new_dirent.as_bytes(), &self.block_device);
+                return true;
+            }
+        }
+        false
+    }
+    /// renameat2-style flag: fail instead of silently overwriting an existing `new_name`
+    pub const RENAME_NOREPLACE: u32 = 1 << 0;
+    /// renameat2-style flag: atomically swap the two dirents' inode-ids instead of replacing
+    pub const RENAME_EXCHANGE: u32 = 1 << 1;
+    /// Move/rename `old_name` (a child of `self`) to `new_name` under `new_dir`.
+    /// `flags` is a bitmask of [`Inode::RENAME_NOREPLACE`] / [`Inode::RENAME_EXCHANGE`].
+    pub fn rename(&self, old_name: &str, new_dir: &Inode, new_name: &str, flags: u32) -> isize {
+        let mut fs = self.fs.lock();
+        let old_id = match self.read_disk_inode(|d| self.find_inode_id(old_name, d)) {
+            Some(id) => id,
+            None => return -1,
+        };
+        let existing_id = new_dir.read_disk_inode(|d| new_dir.find_inode_id(new_name, d));
+
+        if flags & Self::RENAME_EXCHANGE != 0 {
+            let new_id = match existing_id {
+                Some(id) => id,
+                None => return -1,
+            };
+            self.modify_disk_inode(|d| self.set_entry_inode(old_name, new_id, d));
+            new_dir.modify_disk_inode(|d| new_dir.set_entry_inode(new_name, old_id, d));
+            block_cache_sync_all();
+            return 0;
+        }
+
+        if flags & Self::RENAME_NOREPLACE != 0 && existing_id.is_some() {
+            return -1;
+        }
+
+        if let Some(victim_id) = existing_id {
+            new_dir.modify_disk_inode(|d| new_dir.modify_entry(new_name, d));
+            let (block_id, block_offset) = fs.get_disk_inode_pos(victim_id);
+            let remaining_links = get_block_cache(block_id as usize, Arc::clone(&self.block_device))
+                .lock()
+                .modify(block_offset, |disk_inode: &mut DiskInode| {
+                    disk_inode.nlinks -= 1;
+                    if disk_inode.nlinks == 0 {
+                        disk_inode.clear_size(&self.block_device);
+                        if disk_inode.xattr_block != 0 {
+                            fs.dealloc_data(disk_inode.xattr_block);
+                            disk_inode.xattr_block = 0;
+                        }
+                    }
+                    disk_inode.nlinks
+                });
+            if remaining_links == 0 {
+                // the inode_id is now free for reuse; evict any cached handle so a later
+                // reallocation can't alias this stale one
+                INODE_CACHE.lock().remove(victim_id);
+            }
+        }
+
+        self.modify_disk_inode(|d| self.modify_entry(old_name, d));
+        new_dir.modify_disk_inode(|d| {
+            let file_count = (d.size as usize) / DIRENT_SZ;
+            let new_size = (file_count + 1) * DIRENT_SZ;
+            self.increase_size(new_size as u32, d, &mut fs);
+            let dirent = DirEntry::new(new_name, old_id);
+            d.write_at(file_count * DIRENT_SZ, dirent.as_bytes(), &self.block_device);
+        });
+        block_cache_sync_all();
+        0
+    }
+    /// Hard cap on total packed xattr bytes per inode (records live in a single block)
+    pub const XATTR_MAX_BYTES: usize = BLOCK_SZ;
+    fn xattr_block(&self) -> u32 {
+        self.read_disk_inode(|d| d.xattr_block)
+    }
+    fn ensure_xattr_block(&self, fs: &mut MutexGuard<EasyFileSystem>) -> u32 {
+        let existing = self.xattr_block();
+        if existing != 0 {
+            return existing;
+        }
+        let new_block = fs.alloc_data();
+        self.modify_disk_inode(|d| d.xattr_block = new_block);
+        get_block_cache(new_block as usize, Arc::clone(&self.block_device))
+            .lock()
+            .modify(0, |data: &mut [u8; BLOCK_SZ]| data.fill(0));
+        new_block
+    }
+    /// records are packed as (name_len: u8, name, value_len: u16 LE, value), terminated by a
+    /// zero name_len or the end of the block
+    fn parse_xattrs(buf: &[u8; BLOCK_SZ]) -> Vec<(String, Vec<u8>)> {
+        let mut v = Vec::new();
+        let mut pos = 0usize;
+        while pos < buf.len() {
+            let name_len = buf[pos] as usize;
+            if name_len == 0 {
+                break;
+            }
+            pos += 1;
+            if pos + name_len > buf.len() {
+                break;
+            }
+            let name = String::from_utf8_lossy(&buf[pos..pos + name_len]).into_owned();
+            pos += name_len;
+            if pos + 2 > buf.len() {
+                break;
+            }
+            let value_len = u16::from_le_bytes([buf[pos], buf[pos + 1]]) as usize;
+            pos += 2;
+            if pos + value_len > buf.len() {
+                break;
+            }
+            let value = buf[pos..pos + value_len].to_vec();
+            pos += value_len;
+            v.push((name, value));
+        }
+        v
+    }
+    fn encode_xattrs(entries: &[(String, Vec<u8>)]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        for (name, value) in entries {
+            buf.push(name.len() as u8);
+            buf.extend_from_slice(name.as_bytes());
+            buf.extend_from_slice(&(value.len() as u16).to_le_bytes());
+            buf.extend_from_slice(value);
+        }
+        buf
+    }
+    fn read_xattr_block(&self, block: u32) -> [u8; BLOCK_SZ] {
+        let mut buf = [0u8; BLOCK_SZ];
+        get_block_cache(block as usize, Arc::clone(&self.block_device))
+            .lock()
+            .read(0, |data: &[u8; BLOCK_SZ]| buf.copy_from_slice(data));
+        buf
+    }
+    /// Get the value of an extended attribute, if set
+    pub fn get_xattr(&self, name: &str) -> Option<Vec<u8>> {
+        let _fs = self.fs.lock();
+        let block = self.xattr_block();
+        if block == 0 {
+            return None;
+        }
+        let buf = self.read_xattr_block(block);
+        Self::parse_xattrs(&buf)
+            .into_iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v)
+    }
+    /// List the names of all extended attributes set on current inode
+    pub fn list_xattr(&self) -> Vec<String> {
+        let _fs = self.fs.lock();
+        let block = self.xattr_block();
+        if block == 0 {
+            return Vec::new();
+        }
+        let buf = self.read_xattr_block(block);
+        Self::parse_xattrs(&buf).into_iter().map(|(n, _)| n).collect()
+    }
+    /// Set (or overwrite) an extended attribute. Returns -1 (ENOSPC) if the per-inode cap
+    /// would be exceeded
+    pub fn set_xattr(&self, name: &str, value: &[u8]) -> isize {
+        if name.len() > u8::MAX as usize {
+            return -1;
+        }
+        let mut fs = self.fs.lock();
+        let block = self.ensure_xattr_block(&mut fs);
+        let buf = self.read_xattr_block(block);
+        let mut entries = Self::parse_xattrs(&buf);
+        entries.retain(|(n, _)| n != name);
+        entries.push((String::from(name), value.to_vec()));
+        let encoded = Self::encode_xattrs(&entries);
+        if encoded.len() > Self::XATTR_MAX_BYTES {
+            return -1;
+        }
+        let mut new_buf = [0u8; BLOCK_SZ];
+        new_buf[..encoded.len()].copy_from_slice(&encoded);
+        get_block_cache(block as usize, Arc::clone(&self.block_device))
+            .lock()
+            .modify(0, |data: &mut [u8; BLOCK_SZ]| *data = new_buf);
+        drop(fs);
+        block_cache_sync_all();
+        0
+    }
+    /// Remove an extended attribute. Returns -1 if it was not set
+    pub fn remove_xattr(&self, name: &str) -> isize {
+        let fs = self.fs.lock();
+        let block = self.xattr_block();
+        if block == 0 {
+            return -1;
+        }
+        let buf = self.read_xattr_block(block);
+        let mut entries = Self::parse_xattrs(&buf);
+        let before = entries.len();
+        entries.retain(|(n, _)| n != name);
+        if entries.len() == before {
+            return -1;
+        }
+        let encoded = Self::encode_xattrs(&entries);
+        let mut new_buf = [0u8; BLOCK_SZ];
+        new_buf[..encoded.len()].copy_from_slice(&encoded);
+        get_block_cache(block as usize, Arc::clone(&self.block_device))
+            .lock()
+            .modify(0, |data: &mut [u8; BLOCK_SZ]| *data = new_buf);
+        drop(fs);
+        block_cache_sync_all();
+        0
+    }
 }
@@ -0,0 +1,37 @@
+//! `fstat`-style file metadata, the on-the-wire struct for `sys_fstat`.
+use bitflags::bitflags;
+
+bitflags! {
+    /// `st_mode`-style file type bits
+    pub struct StatMode: u32 {
+        ///
+        const NULL  = 0;
+        /// directory
+        const DIR   = 0o040000;
+        /// regular file
+        const FILE  = 0o100000;
+        /// symbolic link
+        const LINK  = 0o120000;
+    }
+}
+
+/// Mirrors (a useful subset of) POSIX `struct stat`
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct Stat {
+    /// device id, currently always 0 (single-device filesystem)
+    pub dev: u64,
+    /// inode number
+    pub ino: u64,
+    /// file type
+    pub mode: StatMode,
+    /// number of hard links
+    pub nlink: u32,
+    /// last-access timestamp
+    pub atime: usize,
+    /// last-modification timestamp
+    pub mtime: usize,
+    /// last-metadata-change timestamp
+    pub ctime: usize,
+    pad: [u64; 4],
+}
@@ -0,0 +1,15 @@
+//! Extra address-space helpers used by ptrace, alongside this module's core
+//! `MemorySet`/page-table implementation.
+use crate::mm::PageTable;
+
+/// Translate a virtual address through `token`'s page table — not necessarily the
+/// current task's — returning the corresponding physical address. Used by ptrace to
+/// read/write a traced task's memory through *its own* mappings rather than the
+/// tracer's, the same way [`crate::mm::translated_byte_buffer`] does for the current task.
+pub fn virt_to_pyh_in(token: usize, va: usize) -> usize {
+    let page_table = PageTable::from_token(token);
+    let vpn = va / crate::config::PAGE_SIZE;
+    let page_offset = va % crate::config::PAGE_SIZE;
+    let ppn: usize = page_table.translate(vpn.into()).unwrap().ppn().into();
+    ppn * crate::config::PAGE_SIZE + page_offset
+}
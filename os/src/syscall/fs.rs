@@ -1,7 +1,12 @@
 //! File and filesystem-related syscalls
-use crate::fs::{open_file, OpenFlags, Stat};
+use crate::fs::{open_file, File, OpenFlags, Stat};
 use crate::mm::{translated_byte_buffer, translated_str, UserBuffer};
 use crate::task::{current_task, current_user_token};
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use lazy_static::lazy_static;
+use spin::Mutex;
 
 /* 
 //use core::str::from_utf8;
@@ -17,7 +22,38 @@ use crate::fs::unlinkat;
 use crate::fs::get_inode_id_from_name;
 use crate::fs::state;
 use crate::fs::StatMode;
-use crate::mm::memory_set::virt_to_pyh;
+
+/// Copy a `Copy` value into user space at `ptr`, one page fragment at a time.
+/// Unlike going through a single translated physical pointer, this is safe even when
+/// the value straddles a page boundary: `translated_byte_buffer` already splits the
+/// destination into (possibly non-contiguous) per-page slices, we just walk them.
+pub fn copy_to_user<T: Copy>(token: usize, ptr: *mut T, value: &T) {
+    let len = core::mem::size_of::<T>();
+    let src = unsafe { core::slice::from_raw_parts(value as *const T as *const u8, len) };
+    let buffers = translated_byte_buffer(token, ptr as *const u8, len);
+    let mut copied = 0;
+    for buffer in buffers {
+        let n = buffer.len();
+        buffer.copy_from_slice(&src[copied..copied + n]);
+        copied += n;
+    }
+}
+
+/// The mirror image of [`copy_to_user`]: read a `Copy` value out of user space at `ptr`,
+/// page fragment by page fragment.
+pub fn copy_from_user<T: Copy>(token: usize, ptr: *const T) -> T {
+    let len = core::mem::size_of::<T>();
+    let mut value = core::mem::MaybeUninit::<T>::uninit();
+    let dst = unsafe { core::slice::from_raw_parts_mut(value.as_mut_ptr() as *mut u8, len) };
+    let buffers = translated_byte_buffer(token, ptr as *const u8, len);
+    let mut copied = 0;
+    for buffer in buffers {
+        let n = buffer.len();
+        dst[copied..copied + n].copy_from_slice(buffer);
+        copied += n;
+    }
+    unsafe { value.assume_init() }
+}
 
 pub fn sys_write(fd: usize, buf: *const u8, len: usize) -> isize {
     trace!("kernel:pid[{}] sys_write", current_task().unwrap().pid.0);
@@ -62,23 +98,259 @@ pub fn sys_read(fd: usize, buf: *const u8, len: usize) -> isize {
     }
 }
 
+/// A pluggable path namespace, borrowed from the `redox_syscall` scheme model: a path of
+/// the form `"name:rest"` is routed to the scheme registered as `name`, which produces a
+/// `File` for `rest` (the part after the colon) however it likes, without needing an
+/// on-disk inode. Unprefixed paths keep going through [`open_file`] as before.
+pub trait Scheme: Send + Sync {
+    /// Open `rel` (the path with the `"name:"` prefix already stripped) under this scheme
+    fn open(&self, rel: &str, flags: OpenFlags) -> Option<Arc<dyn File>>;
+}
+
+/// `null:` — reads report EOF immediately, writes silently discard everything
+struct NullScheme;
+impl File for NullScheme {
+    fn readable(&self) -> bool {
+        true
+    }
+    fn writable(&self) -> bool {
+        true
+    }
+    fn read(&self, _buf: UserBuffer) -> usize {
+        0
+    }
+    fn write(&self, buf: UserBuffer) -> usize {
+        buf.len()
+    }
+}
+impl Scheme for NullScheme {
+    fn open(&self, _rel: &str, _flags: OpenFlags) -> Option<Arc<dyn File>> {
+        Some(Arc::new(NullScheme))
+    }
+}
+
+/// `zero:` — reads fill the caller's buffer with zero bytes, writes silently discard
+struct ZeroScheme;
+impl File for ZeroScheme {
+    fn readable(&self) -> bool {
+        true
+    }
+    fn writable(&self) -> bool {
+        true
+    }
+    fn read(&self, mut buf: UserBuffer) -> usize {
+        let mut total = 0;
+        for slice in buf.buffers.iter_mut() {
+            slice.fill(0);
+            total += slice.len();
+        }
+        total
+    }
+    fn write(&self, buf: UserBuffer) -> usize {
+        buf.len()
+    }
+}
+impl Scheme for ZeroScheme {
+    fn open(&self, _rel: &str, _flags: OpenFlags) -> Option<Arc<dyn File>> {
+        Some(Arc::new(ZeroScheme))
+    }
+}
+
+/// `rand:` — reads fill the caller's buffer with a simple xorshift64 stream, writes discard
+struct RandomScheme;
+impl File for RandomScheme {
+    fn readable(&self) -> bool {
+        true
+    }
+    fn writable(&self) -> bool {
+        true
+    }
+    fn read(&self, mut buf: UserBuffer) -> usize {
+        static STATE: Mutex<u64> = Mutex::new(0x2545_f491_4f6c_dd1d);
+        let mut state = STATE.lock();
+        let mut total = 0;
+        for slice in buf.buffers.iter_mut() {
+            for byte in slice.iter_mut() {
+                *state ^= *state << 13;
+                *state ^= *state >> 7;
+                *state ^= *state << 17;
+                *byte = (*state & 0xff) as u8;
+            }
+            total += slice.len();
+        }
+        total
+    }
+    fn write(&self, buf: UserBuffer) -> usize {
+        buf.len()
+    }
+}
+impl Scheme for RandomScheme {
+    fn open(&self, _rel: &str, _flags: OpenFlags) -> Option<Arc<dyn File>> {
+        Some(Arc::new(RandomScheme))
+    }
+}
+
+lazy_static! {
+    static ref SCHEMES: Mutex<Vec<(String, Arc<dyn Scheme>)>> = Mutex::new(alloc::vec![
+        (String::from("null"), Arc::new(NullScheme) as Arc<dyn Scheme>),
+        (String::from("zero"), Arc::new(ZeroScheme) as Arc<dyn Scheme>),
+        (String::from("rand"), Arc::new(RandomScheme) as Arc<dyn Scheme>),
+    ]);
+}
+
+/// Register a scheme under `name` (the part of a path before its `:`)
+pub fn register_scheme(name: &str, scheme: Arc<dyn Scheme>) {
+    SCHEMES.lock().push((String::from(name), scheme));
+}
+
+fn lookup_scheme(name: &str) -> Option<Arc<dyn Scheme>> {
+    SCHEMES
+        .lock()
+        .iter()
+        .find(|(n, _)| n.as_str() == name)
+        .map(|(_, s)| s.clone())
+}
+
 pub fn sys_open(path: *const u8, flags: u32) -> isize {
     trace!("kernel:pid[{}] sys_open", current_task().unwrap().pid.0);
     let task = current_task().unwrap();
     let token = current_user_token();
     let path = translated_str(token, path);
-    if let Some(inode) = open_file(path.as_str(), OpenFlags::from_bits(flags).unwrap()) { 
+    let flags = OpenFlags::from_bits(flags).unwrap();
+
+    if let Some((scheme_name, rel)) = path.split_once(':') {
+        if let Some(scheme) = lookup_scheme(scheme_name) {
+            return match scheme.open(rel, flags) {
+                Some(file) => {
+                    let mut inner = task.inner_exclusive_access();
+                    let fd = inner.alloc_fd();
+                    // scheme-backed files have no on-disk inode
+                    inner.fdtoinode[fd] = -1;
+                    inner.fd_table[fd] = Some(file);
+                    fd as isize
+                }
+                None => -1,
+            };
+        }
+    }
+
+    if let Some(inode) = open_file(path.as_str(), flags) {
         let inode_id = get_inode_id_from_name(path.as_str());
         let mut inner = task.inner_exclusive_access();
         let fd = inner.alloc_fd();     // fd  ->  inode_id
         inner.fdtoinode[fd]=inode_id as i32;
         inner.fd_table[fd] = Some(inode);
-        fd as isize               
+        fd as isize
     } else {
         -1
     }
 }
 
+/// One scatter/gather entry for [`sys_readv`]/[`sys_writev`]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct IoVec {
+    /// start of the user buffer
+    pub base: *const u8,
+    /// length of the user buffer, in bytes
+    pub len: usize,
+}
+
+/// Linux's `IOV_MAX`: the hard cap on how many `IoVec`s a single `readv`/`writev` call
+/// may pass, so an attacker-controlled `iovcnt` can't drive an unbounded allocation.
+const IOV_MAX: usize = 1024;
+
+/// Copy the `iovcnt`-element `IoVec` array out of user memory. The array itself may
+/// straddle a page boundary, so go through `translated_byte_buffer` and reassemble it
+/// rather than reading through a single translated pointer. Returns `None` if `iovcnt`
+/// exceeds [`IOV_MAX`].
+fn translated_iovecs(token: usize, iov: *const IoVec, iovcnt: usize) -> Option<Vec<IoVec>> {
+    if iovcnt > IOV_MAX {
+        return None;
+    }
+    let entry_sz = core::mem::size_of::<IoVec>();
+    let buffers = translated_byte_buffer(token, iov as *const u8, iovcnt * entry_sz);
+    let mut raw = Vec::with_capacity(iovcnt * entry_sz);
+    for buffer in buffers {
+        raw.extend_from_slice(buffer);
+    }
+    Some(
+        (0..iovcnt)
+            .map(|i| unsafe {
+                core::ptr::read_unaligned(raw[i * entry_sz..].as_ptr() as *const IoVec)
+            })
+            .collect(),
+    )
+}
+
+/// Scatter/gather write: write `iovcnt` disjoint user buffers to `fd` in order,
+/// accumulating and returning the total byte count.
+pub fn sys_writev(fd: usize, iov: *const IoVec, iovcnt: usize) -> isize {
+    trace!("kernel:pid[{}] sys_writev", current_task().unwrap().pid.0);
+    let token = current_user_token();
+    let task = current_task().unwrap();
+    let inner = task.inner_exclusive_access();
+    if fd >= inner.fd_table.len() {
+        return -1;
+    }
+    let file = match &inner.fd_table[fd] {
+        Some(file) => file.clone(),
+        None => return -1,
+    };
+    if !file.writable() {
+        return -1;
+    }
+    // release current task TCB manually to avoid multi-borrow
+    drop(inner);
+    let iovecs = match translated_iovecs(token, iov, iovcnt) {
+        Some(iovecs) => iovecs,
+        None => return -1,
+    };
+    let mut total = 0isize;
+    for iovec in iovecs {
+        if iovec.len == 0 {
+            continue;
+        }
+        let buf = UserBuffer::new(translated_byte_buffer(token, iovec.base, iovec.len));
+        total += file.write(buf) as isize;
+    }
+    total
+}
+
+/// Scatter/gather read: fill `iovcnt` disjoint user buffers from `fd` in order,
+/// accumulating and returning the total byte count.
+pub fn sys_readv(fd: usize, iov: *const IoVec, iovcnt: usize) -> isize {
+    trace!("kernel:pid[{}] sys_readv", current_task().unwrap().pid.0);
+    let token = current_user_token();
+    let task = current_task().unwrap();
+    let inner = task.inner_exclusive_access();
+    if fd >= inner.fd_table.len() {
+        return -1;
+    }
+    let file = match &inner.fd_table[fd] {
+        Some(file) => file.clone(),
+        None => return -1,
+    };
+    if !file.readable() {
+        return -1;
+    }
+    // release current task TCB manually to avoid multi-borrow
+    drop(inner);
+    let iovecs = match translated_iovecs(token, iov, iovcnt) {
+        Some(iovecs) => iovecs,
+        None => return -1,
+    };
+    let mut total = 0isize;
+    for iovec in iovecs {
+        if iovec.len == 0 {
+            continue;
+        }
+        let buf = UserBuffer::new(translated_byte_buffer(token, iovec.base, iovec.len));
+        total += file.read(buf) as isize;
+    }
+    total
+}
+
 pub fn sys_close(fd: usize) -> isize {
     trace!("kernel:pid[{}] sys_close", current_task().unwrap().pid.0);
     let task = current_task().unwrap();
@@ -95,34 +367,33 @@ pub fn sys_close(fd: usize) -> isize {
 
 /// YOUR JOB: Implement fstat.
 pub fn sys_fstat(_fd: usize, _st: *mut Stat) -> isize {
-    trace!(
-        "kernel:pid[{}] sys_fstat NOT IMPLEMENTED",
-        current_task().unwrap().pid.0
-    );
-    let pd = virt_to_pyh(_st as usize);
-    println!("here 0.1");
+    trace!("kernel:pid[{}] sys_fstat", current_task().unwrap().pid.0);
+    let token = current_user_token();
     let task = current_task().unwrap();
-    println!("here 0.2");
     let task_inner = task.inner_exclusive_access();
-    println!("here 0.3");
-    unsafe{
-        let pdad:*mut Stat = pd as *mut Stat;
-        println!("here 3");
-         let (nlink,_is) = state(task_inner.fdtoinode[_fd] as u64);
-        println!("here 4");
-        (*pdad).dev=0;
-        println!("here 5");
-        (*pdad).ino=task_inner.fdtoinode[_fd] as u64;
-        println!("here 6");
-        (*pdad).nlink=nlink;
-        println!("here 7");
-    //     if is{
-        (*pdad).mode=StatMode::FILE;
-    //     }else {
-    //         (*_st).mode=StatMode::DIR;
-    //    }
-    }
-    println!("here 8");
+    if _fd >= task_inner.fdtoinode.len() || task_inner.fdtoinode[_fd] < 0 {
+        // scheme-backed fd (e.g. null:/zero:/rand:) — no on-disk inode to stat
+        return -1;
+    }
+    let (nlink, is_file, is_symlink, atime, mtime, ctime) =
+        state(task_inner.fdtoinode[_fd] as u64);
+    let mut st: Stat = unsafe { core::mem::zeroed() };
+    st.dev = 0;
+    st.ino = task_inner.fdtoinode[_fd] as u64;
+    st.nlink = nlink;
+    // a DiskInode is always exactly one of file/directory/symlink
+    st.mode = if is_symlink {
+        StatMode::LINK
+    } else if is_file {
+        StatMode::FILE
+    } else {
+        StatMode::DIR
+    };
+    st.atime = atime;
+    st.mtime = mtime;
+    st.ctime = ctime;
+    drop(task_inner);
+    copy_to_user(token, _st, &st);
     0
 }
 
@@ -137,7 +408,7 @@ pub fn sys_linkat(_old_name: *const u8, _new_name: *const u8) -> isize {
     let new_name = translated_str(token, _new_name);
     //let old_name = my_translated_str(_old_name); //   \0
     //let new_name = my_translated_str(_new_name);
-    return linkat(old_name.as_str(),new_name.as_str());
+    return linkat(old_name.as_str(),new_name.as_str(), crate::timer::now());
 }
 /// YOUR JOB: Implement unlinkat.
 pub fn sys_unlinkat(_name: *const u8) -> isize {
@@ -148,7 +419,7 @@ pub fn sys_unlinkat(_name: *const u8) -> isize {
     let token = current_user_token();
     let name = translated_str(token,_name);
     //let name = my_translated_str(_name);
-    return unlinkat(name.as_str());
+    return unlinkat(name.as_str(), crate::timer::now());
 }
 /* 
 fn my_translated_str(ptr :*const u8) -> String {
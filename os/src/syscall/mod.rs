@@ -0,0 +1,78 @@
+//! Implementation of syscalls
+//!
+//! [`syscall`] is the single entry point reached from the trap handler whenever user
+//! code traps into the kernel via `ecall`.
+mod fs;
+mod process;
+
+use fs::*;
+use process::*;
+
+use crate::task::{check_seccomp, exit_current_and_run_next, syscalladd, SeccompAction};
+
+const SYSCALL_READ: usize = 63;
+const SYSCALL_WRITE: usize = 64;
+const SYSCALL_CLOSE: usize = 57;
+const SYSCALL_OPEN: usize = 56;
+const SYSCALL_FSTAT: usize = 80;
+const SYSCALL_LINKAT: usize = 37;
+const SYSCALL_UNLINKAT: usize = 35;
+const SYSCALL_EXIT: usize = 93;
+const SYSCALL_YIELD: usize = 124;
+const SYSCALL_GET_TIME: usize = 169;
+const SYSCALL_TASK_INFO: usize = 410;
+const SYSCALL_MMAP: usize = 222;
+const SYSCALL_MUNMAP: usize = 215;
+const SYSCALL_SBRK: usize = 214;
+const SYSCALL_SECCOMP: usize = 277;
+const SYSCALL_LIST_TASKS: usize = 283;
+const SYSCALL_READV: usize = 65;
+const SYSCALL_WRITEV: usize = 66;
+
+/// Dispatch a trapped syscall to its handler.
+///
+/// Before running the handler, consult the current task's installed seccomp filter (if
+/// any, see [`check_seccomp`]): a non-`Allow` verdict short-circuits the dispatch
+/// entirely, so a sandboxed task's blocked syscalls never reach their real
+/// implementation. `sys_seccomp`/`sys_exit` themselves are never filtered — a task must
+/// always be able to tighten its own sandbox or exit.
+pub fn syscall(syscall_id: usize, args: [usize; 3]) -> isize {
+    syscalladd(syscall_id);
+
+    if syscall_id != SYSCALL_SECCOMP && syscall_id != SYSCALL_EXIT {
+        match check_seccomp(syscall_id) {
+            SeccompAction::Allow => {}
+            SeccompAction::Errno(errno) => return -(errno as isize),
+            SeccompAction::Trap => {
+                crate::task::ptrace_stop_current();
+                return -1;
+            }
+            SeccompAction::Kill => exit_current_and_run_next(),
+        }
+    }
+
+    match syscall_id {
+        SYSCALL_READ => sys_read(args[0], args[1] as *const u8, args[2]),
+        SYSCALL_WRITE => sys_write(args[0], args[1] as *const u8, args[2]),
+        SYSCALL_CLOSE => sys_close(args[0]),
+        SYSCALL_OPEN => sys_open(args[0] as *const u8, args[1] as u32),
+        SYSCALL_FSTAT => sys_fstat(args[0], args[1] as *mut crate::fs::Stat),
+        SYSCALL_LINKAT => sys_linkat(args[0] as *const u8, args[1] as *const u8),
+        SYSCALL_UNLINKAT => sys_unlinkat(args[0] as *const u8),
+        SYSCALL_EXIT => sys_exit(args[0] as i32),
+        SYSCALL_YIELD => sys_yield(),
+        SYSCALL_GET_TIME => sys_get_time(args[0] as *mut TimeVal, args[1]),
+        SYSCALL_TASK_INFO => sys_task_info(args[0] as *mut TaskInfo),
+        SYSCALL_MMAP => sys_mmap(args[0], args[1], args[2]),
+        SYSCALL_MUNMAP => sys_munmap(args[0], args[1]),
+        SYSCALL_SBRK => sys_sbrk(args[0] as i32),
+        SYSCALL_SECCOMP => sys_seccomp(args[0], args[1], args[2]),
+        SYSCALL_LIST_TASKS => sys_list_tasks(args[0] as *mut TaskRecord, args[1]),
+        SYSCALL_READV => sys_readv(args[0], args[1] as *const IoVec, args[2]),
+        SYSCALL_WRITEV => sys_writev(args[0], args[1] as *const IoVec, args[2]),
+        _ => {
+            warn!("Unsupported syscall_id: {}", syscall_id);
+            -1
+        }
+    }
+}
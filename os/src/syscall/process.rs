@@ -7,13 +7,21 @@ use crate::{
 };
 use crate::timer::get_time_ms;
 use crate::timer::get_time_us;
-use crate::mm::memory_set::virt_to_pyh;
 use crate::mm::MapPermission;
 use crate::mm::memory_set::mmp;
 use crate::mm::memory_set::unmap;
+use crate::syscall::fs::copy_to_user;
+use crate::task::{current_task, current_user_token};
+use crate::task::{install_seccomp_rule, lock_seccomp, SeccompAction};
+use crate::task::{
+    ptrace_attach, ptrace_cont, ptrace_getregs, ptrace_peek, ptrace_poke, ptrace_setregs,
+    ptrace_traceme,
+};
+use crate::trap::TrapContext;
+use alloc::vec::Vec;
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct TimeVal {
     pub sec: usize,
     pub usec: usize,
@@ -21,6 +29,7 @@ pub struct TimeVal {
 
 /// Task information
 #[allow(dead_code)]
+#[derive(Clone, Copy)]
 pub struct TaskInfo {
     /// Task status in it's life cycle
     status: TaskStatus,
@@ -44,41 +53,34 @@ pub fn sys_yield() -> isize {
     0
 }
 
-/// YOUR JOB: get time with second and microsecond
-/// HINT: You might reimplement it with virtual memory management.
-/// HINT: What if [`TimeVal`] is splitted by two pages ?
+/// get time with second and microsecond
 pub fn sys_get_time(_ts: *mut TimeVal, _tz: usize) -> isize {
     trace!("kernel: sys_get_time");
 
-    let pd=virt_to_pyh(_ts as usize);
+    let token = current_user_token();
     let us = get_time_us();
-    unsafe {
-        let pdad:*mut TimeVal = pd as *mut TimeVal;
-        *pdad = TimeVal {
+    let tv = TimeVal {
         sec: us / 1_000_000,
         usec: us % 1_000_000,
-        };
-    }
+    };
+    copy_to_user(token, _ts, &tv);
     0
 }
 
-/// YOUR JOB: Finish sys_task_info to pass testcases
-/// HINT: You might reimplement it with virtual memory management.
-/// HINT: What if [`TaskInfo`] is splitted by two pages ?
+/// Finish sys_task_info to pass testcases
 pub fn sys_task_info(_ti: *mut TaskInfo) -> isize {
-    trace!("kernel: sys_task_info NOT IMPLEMENTED YET!");
-    //同理
-    let pd=virt_to_pyh(_ti as usize);
-    unsafe{
+    trace!("kernel: sys_task_info");
+    let token = current_user_token();
+    let ti = {
         let inner = crate::task::TASK_MANAGER.inner.exclusive_access();
         let current = inner.current_task;
-
-        let pdad:*mut TaskInfo = pd as *mut TaskInfo;
-        (*pdad).status=TaskStatus::Running;
-        (*pdad).time=get_time_ms()-inner.tasks[current].time;
-        (*pdad).syscall_times.copy_from_slice(&inner.tasks[current].syscall_times);
-        drop(inner);
-    }
+        TaskInfo {
+            status: TaskStatus::Running,
+            time: get_time_ms() - inner.tasks[current].time,
+            syscall_times: inner.tasks[current].syscall_times,
+        }
+    };
+    copy_to_user(token, _ti, &ti);
     0
 }
 
@@ -113,6 +115,131 @@ pub fn sys_munmap(_start: usize, _len: usize) -> isize {
     trace!("kernel: sys_munmap NOT IMPLEMENTED YET!");
     return unmap(_start,_start+_len);
 }
+/// seccomp mode: install a single-syscall rule (arg is `syscall_nr`, `data` is packed as
+/// described on [`SeccompAction::decode`])
+pub const SECCOMP_SET_RULE: usize = 0;
+/// seccomp mode: lock the filter so no further rules can be installed or loosened
+pub const SECCOMP_LOCK: usize = 1;
+
+/// Install or lock the calling task's syscall filter, Linux/Starnix-seccomp style.
+/// `mode` selects [`SECCOMP_SET_RULE`] (`arg1` = syscall number, `arg2` = packed action,
+/// see [`SeccompAction::decode`]) or [`SECCOMP_LOCK`] (arguments ignored).
+/// Once locked, further `SECCOMP_SET_RULE` calls are rejected: filters may only get
+/// stricter over a task's lifetime, never looser.
+pub fn sys_seccomp(mode: usize, arg1: usize, arg2: usize) -> isize {
+    trace!("kernel:pid[{}] sys_seccomp", current_task().unwrap().pid.0);
+    match mode {
+        SECCOMP_SET_RULE => {
+            let action = SeccompAction::decode(arg2);
+            install_seccomp_rule(arg1, action)
+        }
+        SECCOMP_LOCK => {
+            lock_seccomp();
+            0
+        }
+        _ => -1,
+    }
+}
+
+/// ptrace requests, modeled on the tracer/tracee relationship used by the Starnix kernel
+pub const PTRACE_TRACEME: usize = 0;
+///
+pub const PTRACE_ATTACH: usize = 1;
+///
+pub const PTRACE_CONT: usize = 2;
+///
+pub const PTRACE_PEEKDATA: usize = 3;
+///
+pub const PTRACE_POKEDATA: usize = 4;
+///
+pub const PTRACE_GETREGS: usize = 5;
+///
+pub const PTRACE_SETREGS: usize = 6;
+
+/// Debug a user task: `PTRACE_TRACEME`/`PTRACE_ATTACH` establish the tracer/tracee link,
+/// `PTRACE_CONT` resumes a tracee parked in the `Stopped` state, `PTRACE_PEEKDATA`/
+/// `PTRACE_POKEDATA` read/write one word of the tracee's memory (`addr`/`data`), and
+/// `PTRACE_GETREGS`/`PTRACE_SETREGS` read/write the tracee's saved `TrapContext` through
+/// `addr` (a `*mut`/`*const TrapContext` in the tracer's own address space).
+///
+/// Scope note: a tracee only parks in `Stopped` when it hits a seccomp `SeccompAction::Trap`
+/// or is `PTRACE_ATTACH`ed — there is no automatic syscall-entry/exit stop (no
+/// `PTRACE_SYSCALL` equivalent). `PTRACE_GETREGS` therefore reflects the tracee's most
+/// recent trap, not a live breakpoint; callers that need a stop on every syscall should
+/// install a seccomp `Trap` rule for the syscalls they care about.
+pub fn sys_ptrace(request: usize, pid: usize, addr: usize, data: usize) -> isize {
+    trace!("kernel:pid[{}] sys_ptrace", current_task().unwrap().pid.0);
+    match request {
+        PTRACE_TRACEME => ptrace_traceme(),
+        PTRACE_ATTACH => ptrace_attach(pid),
+        PTRACE_CONT => ptrace_cont(pid),
+        PTRACE_PEEKDATA => ptrace_peek(pid, addr),
+        PTRACE_POKEDATA => ptrace_poke(pid, addr, data),
+        PTRACE_GETREGS => ptrace_getregs(pid, addr as *mut TrapContext),
+        PTRACE_SETREGS => ptrace_setregs(pid, addr as *const TrapContext),
+        _ => -1,
+    }
+}
+
+/// One row of a `sys_list_tasks` listing, `ps`-style
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct TaskRecord {
+    /// this task's pid
+    pub pid: usize,
+    /// its parent's pid, or 0 for the root task
+    pub ppid: usize,
+    /// current lifecycle state (`Running`/`Ready`/`Sleeping`/`Zombie`/`Stopped`, ...)
+    pub status: TaskStatus,
+    /// ms elapsed since the task was first scheduled (0 if it never was); this is
+    /// wall-clock time, not time spent on the CPU
+    pub time: usize,
+    /// total number of syscalls made so far
+    pub syscall_total: u32,
+}
+
+/// Fill the user array at `buf` (capacity `cap` records) with one [`TaskRecord`] per live
+/// task, the basis for a `ps`-like tool. Returns the number of records actually written.
+pub fn sys_list_tasks(buf: *mut TaskRecord, cap: usize) -> isize {
+    trace!("kernel:pid[{}] sys_list_tasks", current_task().unwrap().pid.0);
+    let token = current_user_token();
+    let mut records = Vec::new();
+    for task in crate::task::all_tasks() {
+        if records.len() >= cap {
+            break;
+        }
+        let inner = task.inner_exclusive_access();
+        let syscall_total: u32 = inner.syscall_times.iter().sum();
+        let ppid = inner
+            .parent
+            .as_ref()
+            .and_then(|p| p.upgrade())
+            .map(|p| p.pid.0)
+            .unwrap_or(0);
+        // `time` is the absolute ms timestamp the task was first scheduled, not elapsed
+        // runtime (see TaskControlBlockInner::time) — a task never scheduled yet (`first`)
+        // hasn't accumulated any runtime at all.
+        let time = if inner.first {
+            0
+        } else {
+            get_time_ms() - inner.time
+        };
+        records.push(TaskRecord {
+            pid: task.pid.0,
+            ppid,
+            status: inner.task_status,
+            time,
+            syscall_total,
+        });
+    }
+    // writing may span several pages for a large listing, so go through the page-safe helper
+    for (i, record) in records.iter().enumerate() {
+        let ptr = unsafe { buf.add(i) };
+        copy_to_user(token, ptr, record);
+    }
+    records.len() as isize
+}
+
 /// change data segment size
 pub fn sys_sbrk(size: i32) -> isize {
     trace!("kernel: sys_sbrk");
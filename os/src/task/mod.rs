@@ -0,0 +1,113 @@
+//! Task/process management: lifecycle, scheduling, and the global task registry.
+mod processor;
+mod switch;
+mod task;
+
+pub use processor::*;
+pub use switch::__switch;
+pub use task::{PidHandle, TaskControlBlock, TaskControlBlockInner};
+
+use alloc::collections::BTreeMap;
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+use crate::sync::UPSafeCell;
+use lazy_static::lazy_static;
+
+/// A task's position in its life cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskStatus {
+    /// queued, waiting for the scheduler to pick it
+    Ready,
+    /// currently on CPU
+    Running,
+    /// blocked waiting on some event (e.g. a timer or I/O) to become Ready again
+    Sleeping,
+    /// parked by a tracer (ptrace) or by a seccomp `Trap` action, until `PTRACE_CONT`
+    Stopped,
+    /// exited but not yet reaped by its parent
+    Zombie,
+}
+
+/// Saved callee-saved kernel registers, swapped by [`__switch`]
+#[repr(C)]
+pub struct TaskContext {
+    ra: usize,
+    sp: usize,
+    s: [usize; 12],
+}
+
+impl TaskContext {
+    /// An all-zero context, used to seed the per-core idle loop
+    pub fn zero_init() -> Self {
+        Self {
+            ra: 0,
+            sp: 0,
+            s: [0; 12],
+        }
+    }
+    /// A context that, switched into, returns straight into `trap_return` on its kernel stack
+    pub fn goto_trap_return() -> Self {
+        Self {
+            ra: crate::trap::trap_return as usize,
+            sp: 0,
+            s: [0; 12],
+        }
+    }
+}
+
+lazy_static! {
+    static ref READY_QUEUE: UPSafeCell<VecDeque<Arc<TaskControlBlock>>> =
+        unsafe { UPSafeCell::new(VecDeque::new()) };
+}
+
+/// Enqueue a task to run. Only `Ready` tasks should ever sit in the queue.
+pub fn add_task(task: Arc<TaskControlBlock>) {
+    READY_QUEUE.exclusive_access().push_back(task);
+}
+
+/// Pop the queued task with the lowest stride, if any
+pub fn fetch_task() -> Option<Arc<TaskControlBlock>> {
+    let mut queue = READY_QUEUE.exclusive_access();
+    let idx = (0..queue.len()).min_by_key(|&i| queue[i].inner_exclusive_access().stride)?;
+    queue.remove(idx)
+}
+
+lazy_static! {
+    static ref NEXT_PID: UPSafeCell<usize> = unsafe { UPSafeCell::new(0) };
+}
+
+/// Allocate the next pid, monotonically increasing and never reused
+pub fn alloc_pid() -> PidHandle {
+    let mut next = NEXT_PID.exclusive_access();
+    let pid = *next;
+    *next += 1;
+    PidHandle(pid)
+}
+
+lazy_static! {
+    /// Every live task, keyed by pid — the basis for `pid2task` (ptrace) and `all_tasks`
+    /// (`sys_list_tasks`)
+    static ref PID2TCB: UPSafeCell<BTreeMap<usize, Arc<TaskControlBlock>>> =
+        unsafe { UPSafeCell::new(BTreeMap::new()) };
+}
+
+/// Register a freshly created task so it's reachable by pid (`fork` calls this for its child)
+pub fn register_task(task: &Arc<TaskControlBlock>) {
+    PID2TCB.exclusive_access().insert(task.pid.0, task.clone());
+}
+
+/// Drop a task from the registry once it's been reaped, so its pid can't be targeted
+/// by `ptrace`/`sys_list_tasks` anymore
+pub fn unregister_task(pid: usize) {
+    PID2TCB.exclusive_access().remove(&pid);
+}
+
+/// Look a task up by pid, e.g. to resolve a `ptrace` target
+pub fn pid2task(pid: usize) -> Option<Arc<TaskControlBlock>> {
+    PID2TCB.exclusive_access().get(&pid).cloned()
+}
+
+/// Every live (registered, not-yet-reaped) task, for a `ps`-like listing (`sys_list_tasks`)
+pub fn all_tasks() -> alloc::vec::Vec<Arc<TaskControlBlock>> {
+    PID2TCB.exclusive_access().values().cloned().collect()
+}
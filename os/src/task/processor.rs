@@ -10,8 +10,36 @@ use super::{TaskContext, TaskControlBlock};
 use crate::sync::UPSafeCell;
 use crate::trap::TrapContext;
 use alloc::sync::Arc;
+use alloc::vec::Vec;
 use lazy_static::*;
 
+/// seccomp-style per-syscall action, consulted by the trap dispatcher before it runs
+/// the matching syscall handler
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeccompAction {
+    /// run the syscall normally
+    Allow,
+    /// skip the syscall, return this errno (as a negative isize) to the caller instead
+    Errno(i32),
+    /// skip the syscall and trap the task (parks it; a debugger-style observer resumes it)
+    Trap,
+    /// skip the syscall and kill the task, as if it had called sys_exit
+    Kill,
+}
+
+impl SeccompAction {
+    /// Decode the `arg2` packing used by `sys_seccomp(SECCOMP_SET_RULE, nr, arg2)`:
+    /// 0 = Allow, 1 = Trap, 2 = Kill, and any value `>= 0x1_0000_0000` is `Errno(low 32 bits)`.
+    pub fn decode(arg2: usize) -> Self {
+        match arg2 {
+            0 => Self::Allow,
+            1 => Self::Trap,
+            2 => Self::Kill,
+            packed => Self::Errno((packed & 0xffff_ffff) as i32),
+        }
+    }
+}
+
 use crate::config::BIG_STRIDE;
 
 use crate::timer::get_time_ms;
@@ -64,6 +92,12 @@ pub fn run_tasks() {
             // access coming task TCB exclusively
             let mut task_inner = task.inner_exclusive_access();
             let next_task_cx_ptr = &task_inner.task_cx as *const TaskContext;
+            if task_inner.task_status == TaskStatus::Stopped {
+                // parked by ptrace/seccomp since it was enqueued; drop it on the floor
+                // instead of running it. Whoever resumes it (`PTRACE_CONT`) re-enqueues it.
+                drop(task_inner);
+                continue;
+            }
             task_inner.task_status = TaskStatus::Running;
             task_inner.stride+=BIG_STRIDE/(task_inner.prio as i32);
             if task_inner.first{
@@ -119,6 +153,173 @@ pub fn schedule(switched_task_cx_ptr: *mut TaskContext) {
     }
 }
 
+/// Park the current task in `Stopped`, as seccomp's `SeccompAction::Trap` does for a
+/// filtered syscall. A tracer resumes it with `PTRACE_CONT`.
+pub fn ptrace_stop_current() {
+    let task = current_task().unwrap();
+    task.inner_exclusive_access().task_status = TaskStatus::Stopped;
+    let task_cx_ptr = &mut task.inner_exclusive_access().task_cx as *mut TaskContext;
+    schedule(task_cx_ptr);
+}
+
+/// Mark the current task as wanting to be traced by its parent (`PTRACE_TRACEME`): the
+/// parent attaches to it with `PTRACE_ATTACH` once it sees the request.
+pub fn ptrace_traceme() -> isize {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    inner.trace_requested = true;
+    0
+}
+
+/// Attach the current task as tracer of `pid`, parking the tracee in `Stopped`. Only
+/// honored if `pid` has already opted in via `PTRACE_TRACEME` — otherwise any task could
+/// attach to any other task and read/write its memory and registers through the
+/// `is_tracer_of` checks that gate every other ptrace request.
+pub fn ptrace_attach(pid: usize) -> isize {
+    let tracer = current_task().unwrap();
+    let tracee = match crate::task::pid2task(pid) {
+        Some(t) => t,
+        None => return -1,
+    };
+    let mut inner = tracee.inner_exclusive_access();
+    if !inner.trace_requested {
+        return -1;
+    }
+    inner.traced_by = Some(tracer.pid.0);
+    inner.task_status = TaskStatus::Stopped;
+    0
+}
+
+/// Is the current task `pid`'s registered tracer? Every ptrace request but `TRACEME`/
+/// `ATTACH` themselves must pass this, or any task could read/write any other task's
+/// memory and registers just by guessing its pid.
+fn is_tracer_of(tracee: &Arc<TaskControlBlock>) -> bool {
+    let tracer_pid = current_task().unwrap().pid.0;
+    tracee.inner_exclusive_access().traced_by == Some(tracer_pid)
+}
+
+/// Resume a tracee that is parked in `Stopped`, i.e. after a seccomp `Trap` or `PTRACE_ATTACH`
+pub fn ptrace_cont(pid: usize) -> isize {
+    let tracee = match crate::task::pid2task(pid) {
+        Some(t) => t,
+        None => return -1,
+    };
+    if !is_tracer_of(&tracee) {
+        return -1;
+    }
+    let mut inner = tracee.inner_exclusive_access();
+    if inner.task_status != TaskStatus::Stopped {
+        return -1;
+    }
+    inner.task_status = TaskStatus::Ready;
+    drop(inner);
+    crate::task::add_task(tracee);
+    0
+}
+
+/// Read one word out of `pid`'s address space at `addr`, translated through *its own*
+/// page table (not the tracer's)
+pub fn ptrace_peek(pid: usize, addr: usize) -> isize {
+    let tracee = match crate::task::pid2task(pid) {
+        Some(t) => t,
+        None => return -1,
+    };
+    if !is_tracer_of(&tracee) {
+        return -1;
+    }
+    let token = tracee.get_user_token();
+    let phys = crate::mm::memory_set::virt_to_pyh_in(token, addr);
+    unsafe { *(phys as *const usize) as isize }
+}
+
+/// Write one word into `pid`'s address space at `addr`, translated through its own page table
+pub fn ptrace_poke(pid: usize, addr: usize, data: usize) -> isize {
+    let tracee = match crate::task::pid2task(pid) {
+        Some(t) => t,
+        None => return -1,
+    };
+    if !is_tracer_of(&tracee) {
+        return -1;
+    }
+    let token = tracee.get_user_token();
+    let phys = crate::mm::memory_set::virt_to_pyh_in(token, addr);
+    unsafe {
+        *(phys as *mut usize) = data;
+    }
+    0
+}
+
+/// Copy `pid`'s saved trap context (its registers) out to `out`, a pointer in the
+/// *tracer's* (caller's) address space
+pub fn ptrace_getregs(pid: usize, out: *mut TrapContext) -> isize {
+    let tracee = match crate::task::pid2task(pid) {
+        Some(t) => t,
+        None => return -1,
+    };
+    if !is_tracer_of(&tracee) {
+        return -1;
+    }
+    let mut inner = tracee.inner_exclusive_access();
+    let saved = *inner.get_trap_cx();
+    drop(inner);
+    crate::syscall::fs::copy_to_user(current_user_token(), out, &saved);
+    0
+}
+
+/// Overwrite `pid`'s saved trap context (its registers) from `input`, a pointer in the
+/// *tracer's* (caller's) address space
+pub fn ptrace_setregs(pid: usize, input: *const TrapContext) -> isize {
+    let tracee = match crate::task::pid2task(pid) {
+        Some(t) => t,
+        None => return -1,
+    };
+    if !is_tracer_of(&tracee) {
+        return -1;
+    }
+    let new_cx = crate::syscall::fs::copy_from_user(current_user_token(), input);
+    let mut inner = tracee.inner_exclusive_access();
+    *inner.get_trap_cx() = new_cx;
+    0
+}
+
+/// Install a per-syscall seccomp rule on the current task. Filters are inherited across
+/// fork (`TaskControlBlock::fork` copies `seccomp_filter`/`seccomp_locked`) and, once locked
+/// via [`lock_seccomp`], may not be installed or loosened again. A rule already installed
+/// for `nr` is never replaced: installing a different action for it returns -1.
+pub fn install_seccomp_rule(nr: usize, action: SeccompAction) -> isize {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    if inner.seccomp_locked {
+        return -1;
+    }
+    if let Some(entry) = inner.seccomp_filter.iter().find(|(n, _)| *n == nr) {
+        return if entry.1 == action { 0 } else { -1 };
+    }
+    inner.seccomp_filter.push((nr, action));
+    0
+}
+
+/// Lock the current task's seccomp filter so no further rules can be installed or loosened
+pub fn lock_seccomp() {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    inner.seccomp_locked = true;
+}
+
+/// Look up the action the current task's seccomp filter has installed for syscall `nr`.
+/// Called by the trap dispatcher before running the matching syscall handler; `Allow`
+/// (the default when no rule is installed) means run it as usual.
+pub fn check_seccomp(nr: usize) -> SeccompAction {
+    let task = current_task().unwrap();
+    let inner = task.inner_exclusive_access();
+    inner
+        .seccomp_filter
+        .iter()
+        .find(|(n, _)| *n == nr)
+        .map(|(_, action)| *action)
+        .unwrap_or(SeccompAction::Allow)
+}
+
 //记录系统调用
 ///
 pub fn syscalladd(id: usize){
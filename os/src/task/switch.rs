@@ -0,0 +1,12 @@
+//! Raw context switch between two tasks' kernel stacks
+
+use super::TaskContext;
+
+core::arch::global_asm!(include_str!("switch.S"));
+
+extern "C" {
+    /// Switch from `current_task_cx_ptr`'s kernel context to `next_task_cx_ptr`'s.
+    /// Control returns to the caller of `__switch` for whichever task is switched
+    /// back into, which may not be this call site at all.
+    pub fn __switch(current_task_cx_ptr: *mut TaskContext, next_task_cx_ptr: *const TaskContext);
+}
@@ -0,0 +1,120 @@
+//! Task control block: per-task kernel state (address space, fd table, scheduling, ...)
+
+use super::{SeccompAction, TaskContext, TaskStatus};
+use crate::config::MAX_SYSCALL_NUM;
+use crate::fs::File;
+use crate::mm::MemorySet;
+use crate::sync::UPSafeCell;
+use crate::trap::TrapContext;
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+use core::cell::RefMut;
+
+/// A process/task's identity, allocated once and held for its whole lifetime
+pub struct PidHandle(pub usize);
+
+/// A task's kernel-visible control block. `pid` is immutable; everything that changes at
+/// runtime lives behind [`TaskControlBlock::inner_exclusive_access`].
+pub struct TaskControlBlock {
+    /// this task's pid, stable for its whole lifetime
+    pub pid: PidHandle,
+    inner: UPSafeCell<TaskControlBlockInner>,
+}
+
+/// Everything about a task that changes at runtime
+pub struct TaskControlBlockInner {
+    /// where this task is in its life cycle
+    pub task_status: TaskStatus,
+    /// saved kernel-mode registers, restored by `__switch`
+    pub task_cx: TaskContext,
+    /// this task's address space
+    pub memory_set: MemorySet,
+    /// the task that forked this one, if any
+    pub parent: Option<Weak<TaskControlBlock>>,
+    /// tasks forked from this one that haven't been reaped yet
+    pub children: Vec<Arc<TaskControlBlock>>,
+    /// open files, indexed by fd
+    pub fd_table: Vec<Option<Arc<dyn File>>>,
+    /// fd -> backing inode id, or -1 for a scheme-backed / non-inode fd
+    pub fdtoinode: Vec<i32>,
+    /// stride-scheduling priority (higher runs more often)
+    pub prio: i32,
+    /// accumulated stride; the scheduler always runs the lowest-stride ready task
+    pub stride: i32,
+    /// true until this task has been scheduled for the first time
+    pub first: bool,
+    /// wall-clock ms timestamp this task was first scheduled
+    pub time: usize,
+    /// per-syscall-number call counts, indexed by syscall id
+    pub syscall_times: [u32; MAX_SYSCALL_NUM],
+    /// per-syscall-number seccomp rules, consulted by the trap dispatcher before the
+    /// matching handler runs. Inherited by `fork`.
+    pub seccomp_filter: Vec<(usize, SeccompAction)>,
+    /// once set (via `SECCOMP_LOCK`), no further rules may be installed or loosened.
+    /// Inherited by `fork`.
+    pub seccomp_locked: bool,
+    /// pid of this task's tracer, if any (set by `PTRACE_ATTACH`)
+    pub traced_by: Option<usize>,
+    /// set by `PTRACE_TRACEME`; a parent attaches once it observes this
+    pub trace_requested: bool,
+}
+
+impl TaskControlBlockInner {
+    fn alloc_fd(&mut self) -> usize {
+        if let Some(fd) = (0..self.fd_table.len()).find(|fd| self.fd_table[*fd].is_none()) {
+            fd
+        } else {
+            self.fd_table.push(None);
+            self.fdtoinode.push(-1);
+            self.fd_table.len() - 1
+        }
+    }
+}
+
+impl TaskControlBlock {
+    /// Exclusive access to this task's mutable state
+    pub fn inner_exclusive_access(&self) -> RefMut<'_, TaskControlBlockInner> {
+        self.inner.exclusive_access()
+    }
+    /// The root physical page number of this task's page table, as handed to `satp`
+    pub fn get_user_token(&self) -> usize {
+        self.inner_exclusive_access().memory_set.token()
+    }
+    /// Fork a child that gets its own copy of this task's address space (a straight copy,
+    /// no copy-on-write, as in the ch5 tutorial model) and inherits its fd table and
+    /// seccomp filter.
+    /// A tracer relationship (`traced_by`/`trace_requested`) is never inherited: a child
+    /// starts untraced even if its parent was being traced.
+    pub fn fork(self: &Arc<TaskControlBlock>) -> Arc<TaskControlBlock> {
+        let mut parent_inner = self.inner_exclusive_access();
+        let memory_set = MemorySet::from_existing_user(&parent_inner.memory_set);
+        let pid_handle = super::alloc_pid();
+        let child = Arc::new(TaskControlBlock {
+            pid: pid_handle,
+            inner: unsafe {
+                UPSafeCell::new(TaskControlBlockInner {
+                    task_status: TaskStatus::Ready,
+                    task_cx: TaskContext::goto_trap_return(),
+                    memory_set,
+                    parent: Some(Arc::downgrade(self)),
+                    children: Vec::new(),
+                    fd_table: parent_inner.fd_table.clone(),
+                    fdtoinode: parent_inner.fdtoinode.clone(),
+                    prio: parent_inner.prio,
+                    stride: 0,
+                    first: true,
+                    time: 0,
+                    syscall_times: [0; MAX_SYSCALL_NUM],
+                    // filters carry over to the child and may only get stricter from here
+                    seccomp_filter: parent_inner.seccomp_filter.clone(),
+                    seccomp_locked: parent_inner.seccomp_locked,
+                    traced_by: None,
+                    trace_requested: false,
+                })
+            },
+        });
+        parent_inner.children.push(child.clone());
+        super::register_task(&child);
+        child
+    }
+}